@@ -0,0 +1,27 @@
+#![feature(box_syntax)]
+
+mod utils;
+
+mod mutable_debug_assertion;
+mod unnecessary_mut_passed;
+
+use rustc_lint::LintStore;
+use rustc_session::Session;
+use utils::conf;
+
+/// Loads `clippy.toml` (searching the current directory and its ancestors),
+/// falling back to the built-in defaults if none is found. Any parse errors
+/// are surfaced as compiler warnings rather than silently discarded.
+pub fn read_conf(sess: &Session) -> conf::Conf {
+    let file_name = conf::lookup_conf_file();
+    let (conf, warnings) = conf::read(&file_name);
+    for warning in warnings {
+        sess.struct_warn(&warning).emit();
+    }
+    conf
+}
+
+pub fn register_plugins(store: &mut LintStore, conf: &conf::Conf) {
+    store.register_late_pass(|| box mutable_debug_assertion::DebugAssertWithMutCall::new(conf.debug_assert_macros.clone()));
+    store.register_late_pass(|| box unnecessary_mut_passed::UnnecessaryMutPassed);
+}
@@ -1,23 +1,26 @@
-use crate::utils::{is_direct_expn_of, span_lint};
-use if_chain::if_chain;
-use rustc::hir::map::Map;
-use rustc::ty;
-use rustc_hir::intravisit::{walk_expr, NestedVisitorMap, Visitor};
-use rustc_hir::{BorrowKind, Expr, ExprKind, MatchSource, Mutability, StmtKind, UnOp};
+use crate::utils::higher::AssertExpn;
+use crate::utils::usage::{mutates_known_place, MutationKind};
+use crate::utils::{snippet, span_lint, span_lint_and_then};
+use rustc_errors::Applicability;
+use rustc_hir::Expr;
 use rustc_lint::{LateContext, LateLintPass};
-use rustc_session::{declare_lint_pass, declare_tool_lint};
-use rustc_span::Span;
+use rustc_session::{declare_tool_lint, impl_lint_pass};
 
 declare_clippy_lint! {
-    /// **What it does:** Checks for function/method calls with a mutable
-    /// parameter in `debug_assert!`, `debug_assert_eq!` and `debug_assert_ne!` macros.
+    /// **What it does:** Checks for expressions that mutate state inside
+    /// `debug_assert!`, `debug_assert_eq!` and `debug_assert_ne!` macros.
     ///
     /// **Why is this bad?** In release builds `debug_assert!` macros are optimized out by the
     /// compiler.
     /// Therefore mutating something in a `debug_assert!` macro results in different behaviour
     /// between a release and debug build.
     ///
-    /// **Known problems:** None
+    /// **Known problems:** Where it's offered, the suggested fix hoists the mutating
+    /// expression into a `let` binding placed immediately before the assertion. If the
+    /// condition contains more than one mutating expression, their relative evaluation order
+    /// may change, so the suggestion is marked `MaybeIncorrect`. No suggestion is offered at
+    /// all for a `&mut self`/`&mut` parameter receiver autoref (e.g. `it.next()`), since only
+    /// the receiver, not the call, is known there.
     ///
     /// **Example:**
     /// ```rust,ignore
@@ -31,130 +34,60 @@ declare_clippy_lint! {
     "mutable arguments in `debug_assert{,_ne,_eq}!`"
 }
 
-declare_lint_pass!(DebugAssertWithMutCall => [DEBUG_ASSERT_WITH_MUT_CALL]);
-
 const DEBUG_MACRO_NAMES: [&str; 3] = ["debug_assert", "debug_assert_eq", "debug_assert_ne"];
 
-impl<'a, 'tcx> LateLintPass<'a, 'tcx> for DebugAssertWithMutCall {
-    fn check_expr(&mut self, cx: &LateContext<'a, 'tcx>, e: &'tcx Expr<'_>) {
-        for dmn in &DEBUG_MACRO_NAMES {
-            if is_direct_expn_of(e.span, dmn).is_some() {
-                if let Some(span) = extract_call(cx, e) {
-                    span_lint(
-                        cx,
-                        DEBUG_ASSERT_WITH_MUT_CALL,
-                        span,
-                        &format!("do not call a function with mutable arguments inside of `{}!`", dmn),
-                    );
-                }
-            }
-        }
-    }
-}
-
-//HACK(hellow554): remove this when #4694 is implemented
-#[allow(clippy::cognitive_complexity)]
-fn extract_call<'a, 'tcx>(cx: &'a LateContext<'a, 'tcx>, e: &'tcx Expr<'_>) -> Option<Span> {
-    if_chain! {
-        if let ExprKind::Block(ref block, _) = e.kind;
-        if block.stmts.len() == 1;
-        if let StmtKind::Semi(ref matchexpr) = block.stmts[0].kind;
-        then {
-            // debug_assert
-            if_chain! {
-                if let ExprKind::Match(ref ifclause, _, _) = matchexpr.kind;
-                if let ExprKind::DropTemps(ref droptmp) = ifclause.kind;
-                if let ExprKind::Unary(UnOp::UnNot, ref condition) = droptmp.kind;
-                then {
-                    let mut visitor = MutArgVisitor::new(cx);
-                    visitor.visit_expr(condition);
-                    return visitor.expr_span();
-                }
-            }
-
-            // debug_assert_{eq,ne}
-            if_chain! {
-                if let ExprKind::Block(ref matchblock, _) = matchexpr.kind;
-                if let Some(ref matchheader) = matchblock.expr;
-                if let ExprKind::Match(ref headerexpr, _, _) = matchheader.kind;
-                if let ExprKind::Tup(ref conditions) = headerexpr.kind;
-                if conditions.len() == 2;
-                then {
-                    if let ExprKind::AddrOf(BorrowKind::Ref, _, ref lhs) = conditions[0].kind {
-                        let mut visitor = MutArgVisitor::new(cx);
-                        visitor.visit_expr(lhs);
-                        if let Some(span) = visitor.expr_span() {
-                            return Some(span);
-                        }
-                    }
-                    if let ExprKind::AddrOf(BorrowKind::Ref, _, ref rhs) = conditions[1].kind {
-                        let mut visitor = MutArgVisitor::new(cx);
-                        visitor.visit_expr(rhs);
-                        if let Some(span) = visitor.expr_span() {
-                            return Some(span);
-                        }
-                    }
-                }
-            }
-        }
-    }
-
-    None
+/// The `debug_assert_with_mut_call` lint, parameterized over the set of
+/// macro names it considers "debug-only" beyond the built-in
+/// `debug_assert{,_eq,_ne}!`. Extra names come from the `debug_assert_macros`
+/// key in `clippy.toml`.
+pub struct DebugAssertWithMutCall {
+    /// The built-in `DEBUG_MACRO_NAMES` plus any configured `debug_assert_macros`,
+    /// computed once so `check_expr` doesn't rebuild this list for every
+    /// expression in the crate.
+    macro_names: Vec<String>,
 }
 
-struct MutArgVisitor<'a, 'tcx> {
-    cx: &'a LateContext<'a, 'tcx>,
-    expr_span: Option<Span>,
-    found: bool,
-}
-
-impl<'a, 'tcx> MutArgVisitor<'a, 'tcx> {
-    fn new(cx: &'a LateContext<'a, 'tcx>) -> Self {
-        Self {
-            cx,
-            expr_span: None,
-            found: false,
-        }
-    }
-
-    fn expr_span(&self) -> Option<Span> {
-        if self.found {
-            self.expr_span
-        } else {
-            None
-        }
+impl DebugAssertWithMutCall {
+    pub fn new(debug_assert_macros: Vec<String>) -> Self {
+        let mut macro_names: Vec<String> = DEBUG_MACRO_NAMES.iter().map(ToString::to_string).collect();
+        macro_names.extend(debug_assert_macros);
+        Self { macro_names }
     }
 }
 
-impl<'a, 'tcx> Visitor<'tcx> for MutArgVisitor<'a, 'tcx> {
-    type Map = Map<'tcx>;
+impl_lint_pass!(DebugAssertWithMutCall => [DEBUG_ASSERT_WITH_MUT_CALL]);
 
-    fn visit_expr(&mut self, expr: &'tcx Expr<'_>) {
-        match expr.kind {
-            ExprKind::AddrOf(BorrowKind::Ref, Mutability::Mut, _) => {
-                self.found = true;
-                return;
-            },
-            ExprKind::Path(_) => {
-                if let Some(adj) = self.cx.tables.adjustments().get(expr.hir_id) {
-                    if adj
-                        .iter()
-                        .any(|a| matches!(a.target.kind, ty::Ref(_, _, Mutability::Mut)))
-                    {
-                        self.found = true;
-                        return;
+impl<'a, 'tcx> LateLintPass<'a, 'tcx> for DebugAssertWithMutCall {
+    fn check_expr(&mut self, cx: &LateContext<'a, 'tcx>, e: &'tcx Expr<'_>) {
+        if let Some(asserted) = AssertExpn::parse(cx, e, &self.macro_names) {
+            for condition in asserted.exprs() {
+                if let Some(mutation) = mutates_known_place(cx, e.hir_id, condition) {
+                    let msg = "do not mutate or mutably borrow state inside of `debug_assert!`-like macros";
+                    let span = mutation.span;
+                    match mutation.kind {
+                        // `span` covers the whole mutating expression (`&mut expr`, or the
+                        // whole assignment), so hoisting the snippet at `span` into a `let`
+                        // and substituting `__tmp` back in at `span` round-trips.
+                        MutationKind::AddrOfMut | MutationKind::Assign => {
+                            span_lint_and_then(cx, DEBUG_ASSERT_WITH_MUT_CALL, span, msg, |diag| {
+                                let mutating_expr = snippet(cx, span, "..");
+                                diag.multipart_suggestion(
+                                    "evaluate the expression before the assertion, and compare the result instead",
+                                    vec![
+                                        (e.span.shrink_to_lo(), format!("let __tmp = {};\n", mutating_expr)),
+                                        (span, "__tmp".into()),
+                                    ],
+                                    Applicability::MaybeIncorrect,
+                                );
+                            });
+                        },
+                        // `span` only covers the place being borrowed (e.g. a `&mut self`
+                        // receiver autoref), not the call that actually performs the
+                        // mutation, so there's no sound machine-applicable rewrite here.
+                        MutationKind::Other => span_lint(cx, DEBUG_ASSERT_WITH_MUT_CALL, span, msg),
                     }
                 }
-            },
-            // Don't check await desugars
-            ExprKind::Match(_, _, MatchSource::AwaitDesugar) => return,
-            _ if !self.found => self.expr_span = Some(expr.span),
-            _ => return,
+            }
         }
-        walk_expr(self, expr)
-    }
-
-    fn nested_visit_map(&mut self) -> NestedVisitorMap<Self::Map> {
-        NestedVisitorMap::OnlyBodies(self.cx.tcx.hir())
     }
 }
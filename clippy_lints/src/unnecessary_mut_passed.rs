@@ -0,0 +1,81 @@
+use crate::utils::{last_path_segment, span_lint};
+use rustc::ty;
+use rustc::ty::subst::Subst;
+use rustc_hir::{BorrowKind, Expr, ExprKind, Mutability};
+use rustc_lint::{LateContext, LateLintPass};
+use rustc_session::{declare_lint_pass, declare_tool_lint};
+
+declare_clippy_lint! {
+    /// **What it does:** Checks for `&mut` arguments passed to a function or
+    /// method whose corresponding parameter only requires an immutable
+    /// reference.
+    ///
+    /// **Why is this bad?** Requesting a mutable borrow when an immutable one
+    /// would do needlessly widens the caller's exclusivity requirements and
+    /// can be confusing to a reader looking for actual mutation.
+    ///
+    /// **Known problems:** None.
+    ///
+    /// **Example:**
+    /// ```rust,ignore
+    /// // Bad
+    /// let mut vec: Vec<&i32> = Vec::new();
+    /// let mut value = 5;
+    /// vec.push(&mut value); // `push` takes `T`, here `&i32`, not `&mut i32`
+    ///
+    /// // Good
+    /// vec.push(&value);
+    /// ```
+    pub UNNECESSARY_MUT_PASSED,
+    style,
+    "an argument passed as a mutable reference although the function/method only demands an immutable reference"
+}
+
+declare_lint_pass!(UnnecessaryMutPassed => [UNNECESSARY_MUT_PASSED]);
+
+impl<'a, 'tcx> LateLintPass<'a, 'tcx> for UnnecessaryMutPassed {
+    fn check_expr(&mut self, cx: &LateContext<'a, 'tcx>, e: &'tcx Expr<'_>) {
+        match e.kind {
+            ExprKind::Call(ref fn_expr, ref arguments) => {
+                if let ExprKind::Path(ref path) = fn_expr.kind {
+                    let fn_ty = cx.tables.expr_ty(fn_expr);
+                    check_arguments(cx, arguments, fn_ty, &last_path_segment(path).ident.as_str(), "function");
+                }
+            },
+            ExprKind::MethodCall(ref path, _, ref arguments, _) => {
+                let def_id = cx.tables.type_dependent_def_id(e.hir_id).unwrap();
+                let substs = cx.tables.node_substs(e.hir_id);
+                let method_ty = cx.tcx.type_of(def_id).subst(cx.tcx, substs);
+                check_arguments(cx, arguments, method_ty, &path.ident.as_str(), "method");
+            },
+            _ => (),
+        }
+    }
+}
+
+fn check_arguments<'tcx>(
+    cx: &LateContext<'_, 'tcx>,
+    arguments: &[Expr<'_>],
+    fn_ty: ty::Ty<'tcx>,
+    name: &str,
+    fn_kind: &str,
+) {
+    match fn_ty.kind {
+        ty::FnDef(..) | ty::FnPtr(_) => {
+            let parameters = fn_ty.fn_sig(cx.tcx).skip_binder().inputs();
+            for (argument, parameter) in arguments.iter().zip(parameters.iter()) {
+                if let ty::Ref(_, _, Mutability::Not) = parameter.kind {
+                    if let ExprKind::AddrOf(BorrowKind::Ref, Mutability::Mut, _) = argument.kind {
+                        span_lint(
+                            cx,
+                            UNNECESSARY_MUT_PASSED,
+                            argument.span,
+                            &format!("the {} `{}` doesn't need a mutable reference", fn_kind, name),
+                        );
+                    }
+                }
+            }
+        },
+        _ => (),
+    }
+}
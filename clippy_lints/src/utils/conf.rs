@@ -0,0 +1,51 @@
+//! Parses the `clippy.toml` configuration file that lets users tune
+//! individual lints for their crate.
+
+use serde::Deserialize;
+use std::env;
+use std::path::{Path, PathBuf};
+
+/// The actual conf for clippy, merged from both a `clippy.toml` found in the
+/// current directory (or an ancestor) and the hard-coded defaults below.
+#[derive(Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+pub struct Conf {
+    /// Additional macro names, beyond the built-in `debug_assert{,_eq,_ne}!`,
+    /// that expand to the same "compiled out in release builds" shape and
+    /// should be checked by `debug_assert_with_mut_call`.
+    #[serde(default)]
+    pub debug_assert_macros: Vec<String>,
+}
+
+/// Searches the current directory and its ancestors for a `clippy.toml`.
+/// Returns the path it would live at (possibly nonexistent) if none is found,
+/// so callers can treat "not found" the same as "empty config".
+pub fn lookup_conf_file() -> PathBuf {
+    let mut current = env::current_dir().unwrap_or_default();
+    loop {
+        let config_file = current.join("clippy.toml");
+        if config_file.exists() {
+            return config_file;
+        }
+        if !current.pop() {
+            return PathBuf::from("clippy.toml");
+        }
+    }
+}
+
+/// Read the `clippy.toml` configuration file, falling back to `Conf::default()`
+/// if none is found or it fails to parse.
+pub fn read(path: &Path) -> (Conf, Vec<String>) {
+    let mut warnings = Vec::new();
+    let content = match std::fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(_) => return (Conf::default(), warnings),
+    };
+    match toml::from_str(&content) {
+        Ok(conf) => (conf, warnings),
+        Err(e) => {
+            warnings.push(format!("error reading Clippy's configuration file `{}`: {}", path.display(), e));
+            (Conf::default(), warnings)
+        },
+    }
+}
@@ -0,0 +1,93 @@
+//! This module contains helpers for "recovering" the user-written
+//! expressions out of the macro expansions rustc hands us, so individual
+//! lints don't each have to hand-roll a fragile `if_chain!` over the
+//! desugared HIR.
+
+use crate::utils::is_direct_expn_of;
+use if_chain::if_chain;
+use rustc_hir::{BorrowKind, Expr, ExprKind, StmtKind, UnOp};
+use rustc_lint::LateContext;
+
+/// The condition(s) a `{debug_,}assert{,_eq,_ne}!` invocation expands to,
+/// recovered from its macro expansion.
+///
+/// `Assert` carries the single condition passed to `assert!`/`debug_assert!`;
+/// `AssertEq`/`AssertNe` carry the left- and right-hand sides compared by
+/// `assert_eq!`/`assert_ne!` (and their `debug_` counterparts).
+pub enum AssertExpn<'tcx> {
+    Assert(&'tcx Expr<'tcx>),
+    AssertEq(&'tcx Expr<'tcx>, &'tcx Expr<'tcx>),
+    AssertNe(&'tcx Expr<'tcx>, &'tcx Expr<'tcx>),
+}
+
+impl<'tcx> AssertExpn<'tcx> {
+    /// If `e` is a direct expansion of one of the macros in `names`, returns
+    /// the recovered assertion condition(s). `names` may hold any macro names
+    /// (including user-configured ones); a match is recognized by the shape of
+    /// its expansion. A two-operand expansion becomes `AssertNe` when the
+    /// matched name ends in `_ne`, and `AssertEq` otherwise.
+    pub fn parse(cx: &LateContext<'_, 'tcx>, e: &'tcx Expr<'tcx>, names: &[String]) -> Option<Self> {
+        for name in names {
+            if is_direct_expn_of(e.span, name).is_some() {
+                if let Some(expn) = Self::parse_single(e) {
+                    return Some(expn);
+                }
+                if let Some((lhs, rhs)) = Self::parse_pair(e) {
+                    return Some(if name.ends_with("_ne") {
+                        AssertExpn::AssertNe(lhs, rhs)
+                    } else {
+                        AssertExpn::AssertEq(lhs, rhs)
+                    });
+                }
+            }
+        }
+        None
+    }
+
+    // `assert!`/`debug_assert!` expand to (roughly) `{ if !cond { panic!(..) }; }`,
+    // whose `if` is lowered to a `match` on `DropTemps(!cond)`.
+    fn parse_single(e: &'tcx Expr<'tcx>) -> Option<Self> {
+        if_chain! {
+            if let ExprKind::Block(ref block, _) = e.kind;
+            if block.stmts.len() == 1;
+            if let StmtKind::Semi(ref matchexpr) = block.stmts[0].kind;
+            if let ExprKind::Match(ref ifclause, _, _) = matchexpr.kind;
+            if let ExprKind::DropTemps(ref droptmp) = ifclause.kind;
+            if let ExprKind::Unary(UnOp::UnNot, ref cond) = droptmp.kind;
+            then {
+                return Some(AssertExpn::Assert(cond));
+            }
+        }
+        None
+    }
+
+    // `assert_eq!`/`assert_ne!` expand to a nested block matching on
+    // `(&left_val, &right_val)`.
+    fn parse_pair(e: &'tcx Expr<'tcx>) -> Option<(&'tcx Expr<'tcx>, &'tcx Expr<'tcx>)> {
+        if_chain! {
+            if let ExprKind::Block(ref block, _) = e.kind;
+            if block.stmts.len() == 1;
+            if let StmtKind::Semi(ref matchexpr) = block.stmts[0].kind;
+            if let ExprKind::Block(ref matchblock, _) = matchexpr.kind;
+            if let Some(ref matchheader) = matchblock.expr;
+            if let ExprKind::Match(ref headerexpr, _, _) = matchheader.kind;
+            if let ExprKind::Tup(ref conditions) = headerexpr.kind;
+            if conditions.len() == 2;
+            if let ExprKind::AddrOf(BorrowKind::Ref, _, ref lhs) = conditions[0].kind;
+            if let ExprKind::AddrOf(BorrowKind::Ref, _, ref rhs) = conditions[1].kind;
+            then {
+                return Some((lhs, rhs));
+            }
+        }
+        None
+    }
+
+    /// The condition expression(s) making up this assertion, as a `Vec`
+    /// suitable for iterating regardless of which variant was matched.
+    pub fn exprs(&self) -> Vec<&'tcx Expr<'tcx>> {
+        match *self {
+            AssertExpn::Assert(cond) => vec![cond],
+            AssertExpn::AssertEq(lhs, rhs) | AssertExpn::AssertNe(lhs, rhs) => vec![lhs, rhs],
+        }
+    }
+}
@@ -0,0 +1,96 @@
+//! Utilities for detecting whether evaluating an expression can mutate
+//! state the surrounding code depends on, built on top of rustc's
+//! `ExprUseVisitor` rather than pattern-matching individual `ExprKind`s.
+
+use rustc::middle::expr_use_visitor::{Delegate, ExprUseVisitor, Place};
+use rustc::ty::{self, TyCtxt};
+use rustc_hir::{Expr, HirId};
+use rustc_lint::LateContext;
+use rustc_span::Span;
+
+/// Whether the text at [`Mutation::span`] is safe to lift verbatim into a
+/// `let __tmp = <span>;` binding and have `__tmp` substituted back at that
+/// same span.
+#[derive(PartialEq, Eq, Debug)]
+pub enum MutationKind {
+    /// An explicit `&mut expr`: the span covers the whole borrow expression,
+    /// so hoisting it and substituting `__tmp` back in round-trips.
+    AddrOfMut,
+    /// A direct or compound assignment (`x = y`, `x += 1`): the span covers
+    /// the whole assignment expression.
+    Assign,
+    /// Any other access reported as a mutable borrow — most commonly the
+    /// implicit autoref of a `&mut self`/`&mut` parameter receiver (e.g.
+    /// `it.next()`). The span here covers only the *place* being borrowed
+    /// (`it`), not the call that should actually be hoisted (`it.next()`),
+    /// so rewriting it verbatim is unsound: it would leave the mutation
+    /// inside the assertion and move the receiver out from under it.
+    Other,
+}
+
+pub struct Mutation {
+    pub span: Span,
+    pub kind: MutationKind,
+}
+
+/// Returns the first expression that mutates, or takes a mutable borrow of,
+/// a place while `expr` is being evaluated. Catches direct assignments,
+/// compound assignments and `&mut` borrows (including the implicit autoref
+/// on a `&mut self`/`&mut` parameter), unlike checking for
+/// `ExprKind::AddrOf(_, Mutability::Mut, _)` alone.
+///
+/// This is borrow-check-level mutation detection: it does not see through
+/// interior mutability. `cell.set(..)` and `refcell.borrow_mut()` only ever
+/// take `&self`, so `ExprUseVisitor` reports a shared borrow of `cell`, not a
+/// mutation, and those calls are *not* flagged here.
+///
+/// `from_expr` is any expression inside the body to analyze; its enclosing
+/// item is used to scope the `ExprUseVisitor`.
+pub fn mutates_known_place<'a, 'tcx>(
+    cx: &LateContext<'a, 'tcx>,
+    from_expr: HirId,
+    expr: &'tcx Expr<'tcx>,
+) -> Option<Mutation> {
+    let owner = cx.tcx.hir().enclosing_body_owner(from_expr);
+    let def_id = cx.tcx.hir().local_def_id(owner);
+    let mut delegate = MutationDelegate { tcx: cx.tcx, mutation: None };
+    ExprUseVisitor::new(&mut delegate, cx.tcx, def_id, cx.param_env, cx.tables).consume_expr(expr);
+    delegate.mutation
+}
+
+struct MutationDelegate<'tcx> {
+    tcx: TyCtxt<'tcx>,
+    mutation: Option<Mutation>,
+}
+
+impl<'tcx> MutationDelegate<'tcx> {
+    fn record(&mut self, diag_expr_id: HirId, kind: MutationKind) {
+        if self.mutation.is_some() {
+            return;
+        }
+        let span = self.tcx.hir().span(diag_expr_id);
+        self.mutation = Some(Mutation { span, kind });
+    }
+}
+
+impl<'tcx> Delegate<'tcx> for MutationDelegate<'tcx> {
+    fn consume(&mut self, _place: &Place<'tcx>, _diag_expr_id: HirId) {}
+
+    fn borrow(&mut self, _place: &Place<'tcx>, diag_expr_id: HirId, bk: ty::BorrowKind) {
+        if let ty::BorrowKind::MutBorrow = bk {
+            let is_addr_of_mut = matches!(
+                self.tcx.hir().find(diag_expr_id),
+                Some(rustc_hir::Node::Expr(rustc_hir::Expr {
+                    kind: rustc_hir::ExprKind::AddrOf(rustc_hir::BorrowKind::Ref, rustc_hir::Mutability::Mut, _),
+                    ..
+                }))
+            );
+            let kind = if is_addr_of_mut { MutationKind::AddrOfMut } else { MutationKind::Other };
+            self.record(diag_expr_id, kind);
+        }
+    }
+
+    fn mutate(&mut self, _assignee_place: &Place<'tcx>, diag_expr_id: HirId) {
+        self.record(diag_expr_id, MutationKind::Assign);
+    }
+}